@@ -0,0 +1,296 @@
+use crate::AST;
+use std::collections::BTreeSet;
+
+/// Parse a literal into the backend's machine-integer domain. The interpreter
+/// carries arbitrary-precision integers and decimals; this backend lowers to
+/// `i64`, so anything outside that domain is refused rather than lowered to a
+/// value that would diverge from the interpreter.
+fn lower_literal(contents: &str) -> Result<i64, String> {
+    contents.parse::<i64>().map_err(|_| {
+        format!(
+            "cannot lower literal `{}`: the LLVM backend is limited to 64-bit integers",
+            contents
+        )
+    })
+}
+
+fn is_arithmetic(label: &str) -> bool {
+    matches!(
+        label,
+        "+" | "-" | "*" | "%" | "==" | "!=" | "=<" | "<"
+    )
+}
+
+pub fn emit(ast: &AST) -> Result<String, String> {
+    let mut codegen = Codegen::new();
+    let mut slots = BTreeSet::new();
+    collect_slots(ast, &mut slots);
+    codegen.prologue(&slots);
+    codegen.lower(ast)?;
+    codegen.epilogue();
+    Ok(codegen.output)
+}
+
+fn collect_slots(ast: &AST, slots: &mut BTreeSet<String>) {
+    match ast {
+        AST::Scope(obj) => collect_slots(obj, slots),
+        AST::Arrow(a, b) | AST::Match(a, b) => {
+            collect_slots(a, slots);
+            collect_slots(b, slots);
+        }
+        AST::Method(args, obj) => {
+            if let AST::Primitive(label) = obj.as_ref()
+                && (label == "store" || label == "load")
+                && let Some(AST::Literal(name)) = args.first()
+            {
+                slots.insert(name.clone());
+            }
+            for arg in args {
+                collect_slots(arg, slots);
+            }
+        }
+        _ => {}
+    }
+}
+
+struct Codegen {
+    output: String,
+    tmp: usize,
+    label: usize,
+    fail: Vec<String>,
+}
+
+impl Codegen {
+    fn new() -> Self {
+        Codegen {
+            output: String::new(),
+            tmp: 0,
+            label: 0,
+            fail: Vec::new(),
+        }
+    }
+
+    fn tmp(&mut self) -> String {
+        self.tmp += 1;
+        format!("%t{}", self.tmp)
+    }
+
+    fn label(&mut self, tag: &str) -> String {
+        self.label += 1;
+        format!("{}{}", tag, self.label)
+    }
+
+    fn line(&mut self, text: &str) {
+        self.output.push_str("  ");
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    fn block(&mut self, name: &str) {
+        self.output.push_str(name);
+        self.output.push_str(":\n");
+    }
+
+    fn prologue(&mut self, slots: &BTreeSet<String>) {
+        self.output.push_str("declare i32 @printf(i8*, ...)\n");
+        self.output.push_str("declare i32 @scanf(i8*, ...)\n");
+        self.output
+            .push_str("@.fmt = private constant [6 x i8] c\"%lld\\0A\\00\"\n");
+        self.output
+            .push_str("@.scan = private constant [5 x i8] c\"%lld\\00\"\n\n");
+        self.output.push_str("define i32 @main() {\n");
+        self.line("%stream = alloca i64");
+        self.line("store i64 0, i64* %stream");
+        for slot in slots {
+            self.line(&format!("%slot.{} = alloca i64", slot));
+        }
+        self.fail.push("trap".to_string());
+    }
+
+    fn epilogue(&mut self) {
+        self.line("ret i32 0");
+        self.block("trap");
+        self.line("ret i32 1");
+        self.output.push_str("}\n");
+    }
+
+    /// Lower a node in stream position: it reads/writes `%stream` and may
+    /// branch to the enclosing failure label on a non-match.
+    fn lower(&mut self, ast: &AST) -> Result<(), String> {
+        match ast {
+            AST::Scope(obj) => self.lower(obj),
+            AST::Arrow(obj1, obj2) => {
+                self.lower(obj1)?;
+                self.lower(obj2)
+            }
+            AST::Match(obj1, obj2) => {
+                let rhs = self.label("match.rhs");
+                let end = self.label("match.end");
+                // Snapshot the stream so the right branch runs against the
+                // original value, matching the interpreter's cloned stream.
+                let saved = self.tmp();
+                self.line(&format!("{} = load i64, i64* %stream", saved));
+                self.fail.push(rhs.clone());
+                self.lower(obj1)?;
+                self.fail.pop();
+                self.line(&format!("br label %{}", end));
+                self.block(&rhs);
+                self.line(&format!("store i64 {}, i64* %stream", saved));
+                self.lower(obj2)?;
+                self.line(&format!("br label %{}", end));
+                self.block(&end);
+                Ok(())
+            }
+            AST::Method(args, obj) => self.lower_method(args, obj),
+            AST::Primitive(label) => self.lower_primitive(&[], label),
+            AST::Literal(contents) => {
+                let value = lower_literal(contents)?;
+                self.line(&format!("store i64 {}, i64* %stream", value));
+                Ok(())
+            }
+            AST::Variable(label) => Err(format!("cannot lower unbound name `{}`", label)),
+        }
+    }
+
+    fn lower_method(&mut self, args: &[AST], obj: &AST) -> Result<(), String> {
+        match obj {
+            AST::Primitive(label) => self.lower_primitive(args, label),
+            _ => self.lower(obj),
+        }
+    }
+
+    fn lower_primitive(&mut self, args: &[AST], label: &str) -> Result<(), String> {
+        match label {
+            "pass" | "int" | "str" => Ok(()),
+            "output" => {
+                let value = self.tmp();
+                self.line(&format!("{} = load i64, i64* %stream", value));
+                let call = self.tmp();
+                self.line(&format!(
+                    "{} = call i32 (i8*, ...) @printf(i8* getelementptr ([6 x i8], [6 x i8]* @.fmt, i64 0, i64 0), i64 {})",
+                    call, value
+                ));
+                self.line("store i64 0, i64* %stream");
+                Ok(())
+            }
+            "input" => {
+                let call = self.tmp();
+                self.line(&format!(
+                    "{} = call i32 (i8*, ...) @scanf(i8* getelementptr ([5 x i8], [5 x i8]* @.scan, i64 0, i64 0), i64* %stream)",
+                    call
+                ));
+                Ok(())
+            }
+            "store" => {
+                let name = self.slot_name(args)?;
+                let value = self.tmp();
+                self.line(&format!("{} = load i64, i64* %stream", value));
+                self.line(&format!("store i64 {}, i64* %slot.{}", value, name));
+                self.line("store i64 0, i64* %stream");
+                Ok(())
+            }
+            "load" => {
+                let name = self.slot_name(args)?;
+                let value = self.tmp();
+                self.line(&format!("{} = load i64, i64* %slot.{}", value, name));
+                self.line(&format!("store i64 {}, i64* %stream", value));
+                Ok(())
+            }
+            "+" | "-" | "*" | "%" => {
+                let lhs = self.eval(&args[0])?;
+                let rhs = self.eval(&args[1])?;
+                let op = match label {
+                    "+" => "add",
+                    "-" => "sub",
+                    "*" => "mul",
+                    _ => "srem",
+                };
+                let result = self.tmp();
+                self.line(&format!("{} = {} i64 {}, {}", result, op, lhs, rhs));
+                self.line(&format!("store i64 {}, i64* %stream", result));
+                Ok(())
+            }
+            "==" | "!=" | "=<" | "<" => {
+                let lhs = self.eval(&args[0])?;
+                let rhs = self.eval(&args[1])?;
+                let cond = match label {
+                    "==" => "eq",
+                    "!=" => "ne",
+                    "=<" => "sle",
+                    _ => "slt",
+                };
+                let flag = self.tmp();
+                self.line(&format!("{} = icmp {} i64 {}, {}", flag, cond, lhs, rhs));
+                let keep = self.label("cmp.ok");
+                let fail = self.fail.last().cloned().expect("failure target");
+                self.line(&format!("br i1 {}, label %{}, label %{}", flag, keep, fail));
+                self.block(&keep);
+                Ok(())
+            }
+            "loop" => {
+                let head = self.label("loop.head");
+                let end = self.label("loop.end");
+                // `loop` iterates until its body stops matching and then yields
+                // `None`, so once the body fails we leave the loop through the
+                // enclosing failure target rather than falling through as a
+                // success — mirroring the interpreter's `.loop` result.
+                let outer = self.fail.last().cloned().expect("failure target");
+                self.line(&format!("br label %{}", head));
+                self.block(&head);
+                self.fail.push(end.clone());
+                self.lower(&args[0])?;
+                self.fail.pop();
+                self.line(&format!("br label %{}", head));
+                self.block(&end);
+                self.line(&format!("br label %{}", outer));
+                Ok(())
+            }
+            _ => Err(format!("cannot lower primitive `{}`", label)),
+        }
+    }
+
+    /// Lower a node in value position, returning the register holding its i64.
+    fn eval(&mut self, ast: &AST) -> Result<String, String> {
+        match ast {
+            AST::Scope(obj) => self.eval(obj),
+            AST::Literal(contents) => {
+                let value = lower_literal(contents)?;
+                let reg = self.tmp();
+                self.line(&format!("{} = add i64 0, {}", reg, value));
+                Ok(reg)
+            }
+            AST::Arrow(obj1, obj2) => {
+                // The right side is a coercion (`int`/`str`) over the left value.
+                match obj2.as_ref() {
+                    AST::Primitive(p) if p == "int" || p == "str" || p == "pass" => {
+                        self.eval(obj1)
+                    }
+                    _ => Err("cannot lower compound expression in value position".to_string()),
+                }
+            }
+            AST::Method(args, obj) => match obj.as_ref() {
+                AST::Primitive(label) if label == "load" => {
+                    let name = self.slot_name(args)?;
+                    let reg = self.tmp();
+                    self.line(&format!("{} = load i64, i64* %slot.{}", reg, name));
+                    Ok(reg)
+                }
+                AST::Primitive(label) if is_arithmetic(label) => {
+                    self.lower_primitive(args, label)?;
+                    let reg = self.tmp();
+                    self.line(&format!("{} = load i64, i64* %stream", reg));
+                    Ok(reg)
+                }
+                _ => Err("cannot lower method in value position".to_string()),
+            },
+            _ => Err("cannot lower expression in value position".to_string()),
+        }
+    }
+
+    fn slot_name(&mut self, args: &[AST]) -> Result<String, String> {
+        match args.first() {
+            Some(AST::Literal(name)) => Ok(name.clone()),
+            _ => Err("store/load requires a literal slot name".to_string()),
+        }
+    }
+}
@@ -1,10 +1,77 @@
+mod codegen;
+
+use bigdecimal::BigDecimal;
+use num_bigint::{BigInt, ToBigInt};
 use std::collections::HashMap;
 use std::env;
 use std::fs::File;
 use std::io::Read;
+use std::ops::Range;
 use std::process;
 
 #[derive(Debug, PartialEq)]
+enum Error {
+    UnexpectedToken(Range<usize>, String),
+    BadVariableForm(Range<usize>),
+    TypeMismatch(Range<usize>, String),
+    UnboundName(Range<usize>, String),
+    RuntimeTrap(Range<usize>, String),
+}
+
+impl Error {
+    fn span(&self) -> &Range<usize> {
+        match self {
+            Error::UnexpectedToken(span, _)
+            | Error::BadVariableForm(span)
+            | Error::TypeMismatch(span, _)
+            | Error::UnboundName(span, _)
+            | Error::RuntimeTrap(span, _) => span,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            Error::UnexpectedToken(_, msg) => format!("unexpected token: {}", msg),
+            Error::BadVariableForm(_) => "`\\` must be followed by a name".to_string(),
+            Error::TypeMismatch(_, msg) => format!("type mismatch: {}", msg),
+            Error::UnboundName(_, name) => format!("unbound name: `{}`", name),
+            Error::RuntimeTrap(_, msg) => format!("runtime trap: {}", msg),
+        }
+    }
+}
+
+/// Recover a span for an error raised without access to the source (e.g. an
+/// `UnboundName` trap from the interpreter) by locating its token.
+fn anchored(error: Error, source: &str) -> Error {
+    match error {
+        Error::UnboundName(_, name) => {
+            Error::UnboundName(locate(source, &format!(r"\{}", name)), name)
+        }
+        other => other,
+    }
+}
+
+fn report(source: &str, error: &Error) {
+    let span = error.span();
+    let start = span.start.min(source.len());
+    let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = source[start..]
+        .find('\n')
+        .map(|i| start + i)
+        .unwrap_or(source.len());
+    let line_no = source[..line_start].matches('\n').count() + 1;
+    let caret_pad = start - line_start;
+    let caret_len = span.len().max(1);
+    eprintln!("error: {}", error.message());
+    eprintln!("{:>4} | {}", line_no, &source[line_start..line_end]);
+    eprintln!(
+        "     | {}{}",
+        " ".repeat(caret_pad),
+        "^".repeat(caret_len)
+    );
+}
+
+#[derive(Debug, PartialEq, Clone)]
 enum AST {
     Scope(Box<AST>),
     Arrow(Box<AST>, Box<AST>),
@@ -15,22 +82,89 @@ enum AST {
     Variable(String),
 }
 
-fn parse_ast(input: &str) -> Result<AST, ()> {
+fn parse_ast(input: &str) -> Result<AST, Error> {
     let mut tokenizer = kohaku::Tokenizer::new([
-        ";", "|", "->", "<-", "=<", "==", "!=", "<", "+", "-", "*", "%", "\\", ".", "(", ")",
+        ";", "|", ":=", "->", "<-", "=<", "==", "!=", "<", "+", "-", "*", "%", "\\", ".", "(", ")",
     ]);
     let mut parser = suzuran::Parser::new([
-        ";", "|", "->", "<-", "=<", "==", "!=", "<", "+", "-", "*", "%", "\\", ".",
+        ";", "|", ":=", "->", "<-", "=<", "==", "!=", "<", "+", "-", "*", "%", "\\", ".",
     ]);
     let iter = tokenizer.tokenize(input).map_while(|x| x.ok());
-    let node = parser.parse(iter).ok_or(())?;
-    convert(node)
+    let node = parser
+        .parse(iter)
+        .ok_or_else(|| {
+            Error::UnexpectedToken(first_token_span(input), "malformed program".to_string())
+        })?;
+    convert(node, input)
+}
+
+/// Byte range of the first occurrence of `token` in `source`, falling back
+/// to the whole input when the token cannot be located.
+fn locate(source: &str, token: &str) -> Range<usize> {
+    match source.find(token) {
+        Some(start) => start..start + token.len(),
+        None => 0..source.len(),
+    }
+}
+
+/// Byte range of the first occurrence of `token` at or after `from`. The
+/// `suzuran` parser discards byte offsets, so diagnostics recover them by
+/// scanning the source forward in traversal order; threading `from` keeps a
+/// repeated token (`("2" -> int) + ("2" -> str)`) anchored on the right one.
+/// A synthetic token with no source spelling (e.g. the `def` that `:=` lowers
+/// to) falls back to a zero-width span at the cursor, never searching
+/// backwards past tokens already attributed.
+fn locate_from(source: &str, from: usize, token: &str) -> Range<usize> {
+    let from = from.min(source.len());
+    match source[from..].find(token) {
+        Some(offset) => from + offset..from + offset + token.len(),
+        None => from..from,
+    }
 }
 
-fn convert(node: suzuran::Node) -> Result<AST, ()> {
+/// The first non-whitespace token in `source`, used to anchor errors raised
+/// before a node exists (a malformed parse or an empty node).
+fn first_token_span(source: &str) -> Range<usize> {
+    match source.char_indices().find(|(_, c)| !c.is_whitespace()) {
+        Some((start, c)) => start..start + c.len_utf8(),
+        None => 0..source.len(),
+    }
+}
+
+/// Source spelling of a node's leading token, used to anchor diagnostics.
+fn leading_token(ast: &AST) -> Option<String> {
+    match ast {
+        AST::Scope(obj) => leading_token(obj),
+        AST::Arrow(obj1, _) | AST::Match(obj1, _) => leading_token(obj1),
+        AST::Method(args, obj) => args.first().and_then(leading_token).or_else(|| leading_token(obj)),
+        AST::Primitive(label) => Some(label.clone()),
+        AST::Literal(contents) => Some(format!(r#""{}""#, contents)),
+        AST::Variable(label) => Some(format!(r"\{}", label)),
+    }
+}
+
+/// Locate a node's leading token at or after `from`, the cursor position
+/// reached just before the node was checked.
+fn leading_span(source: &str, from: usize, ast: &AST) -> Range<usize> {
+    match leading_token(ast) {
+        Some(token) => locate_from(source, from, &token),
+        None => first_token_span(source),
+    }
+}
+
+/// Advance `cursor` past the next occurrence of `token` and return its span.
+fn consume(source: &str, cursor: &mut usize, token: &str) -> Range<usize> {
+    let span = locate_from(source, *cursor, token);
+    *cursor = span.end;
+    span
+}
+
+fn convert(node: suzuran::Node, source: &str) -> Result<AST, Error> {
     match node {
-        suzuran::Node::Placeholder() => Err(()),
-        suzuran::Node::Parentheses(n) => Ok(AST::Scope(Box::new(convert(*n)?))),
+        suzuran::Node::Placeholder() => {
+            Err(Error::UnexpectedToken(first_token_span(source), "empty node".to_string()))
+        }
+        suzuran::Node::Parentheses(n) => Ok(AST::Scope(Box::new(convert(*n, source)?))),
         suzuran::Node::Primitive(label) => match label.starts_with(r#"""#) {
             true => Ok(AST::Literal(label.trim_matches('"').to_string())),
             false => Ok(AST::Primitive(label)),
@@ -41,17 +175,18 @@ fn convert(node: suzuran::Node) -> Result<AST, ()> {
             {
                 Ok(AST::Variable(label))
             } else {
-                Err(())
+                Err(Error::BadVariableForm(locate(source, "\\")))
             }
         }
         suzuran::Node::Operator(label, n1, n2) => {
-            let a1 = convert(*n1)?;
-            let a2 = convert(*n2)?;
+            let a1 = convert(*n1, source)?;
+            let a2 = convert(*n2, source)?;
             match label.as_str() {
                 ";" => Ok(AST::Arrow(Box::new(a1), Box::new(a2))),
                 "->" => Ok(AST::Arrow(Box::new(a1), Box::new(a2))),
                 "<-" => Ok(AST::Arrow(Box::new(a2), Box::new(a1))),
                 "|" => Ok(AST::Match(Box::new(a1), Box::new(a2))),
+                ":=" => Ok(AST::Method(vec![a1, a2], Box::new(AST::Primitive("def".to_string())))),
                 "." => Ok(AST::Method(vec![a1], Box::new(a2))),
                 _ => Ok(AST::Method(vec![a1, a2], Box::new(AST::Primitive(label)))),
             }
@@ -59,13 +194,171 @@ fn convert(node: suzuran::Node) -> Result<AST, ()> {
     }
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Type {
+    Int,
+    Float,
+    Str,
+    Void,
+    Any,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Signature {
+    input: Type,
+    output: Type,
+}
+
+fn unify(a: Type, b: Type) -> Option<Type> {
+    match (a, b) {
+        (x, y) if x == y => Some(x),
+        // `Any` ranges over the value types (`Int`/`Float`/`Str`); `Void` is
+        // the empty stream and only unifies with itself, so a sink typed
+        // `Any -> _` rejects a `Void` input before it traps at runtime.
+        (Type::Void, _) | (_, Type::Void) => None,
+        (Type::Any, y) => Some(y),
+        (x, Type::Any) => Some(x),
+        // `Int` promotes to `Float`, matching the interpreter's numeric tower.
+        (Type::Int, Type::Float) | (Type::Float, Type::Int) => Some(Type::Float),
+        _ => None,
+    }
+}
+
+fn signature(label: &str) -> Option<Signature> {
+    let sig = |input, output| Signature { input, output };
+    Some(match label {
+        "int" => sig(Type::Any, Type::Int),
+        "float" => sig(Type::Any, Type::Float),
+        "str" => sig(Type::Any, Type::Str),
+        "output" => sig(Type::Any, Type::Void),
+        "input" => sig(Type::Void, Type::Str),
+        "+" | "-" | "*" | "%" => sig(Type::Void, Type::Int),
+        "==" | "!=" | "=<" | "<" => sig(Type::Void, Type::Void),
+        "store" => sig(Type::Any, Type::Void),
+        "load" => sig(Type::Void, Type::Any),
+        "def" => sig(Type::Void, Type::Void),
+        "loop" | "pass" => sig(Type::Any, Type::Any),
+        _ => return None,
+    })
+}
+
+fn check(ast: &AST, source: &str) -> Result<Signature, Error> {
+    check_spanned(ast, source, &mut 0)
+}
+
+/// Type-check `ast` while advancing `cursor` through `source` in traversal
+/// order, so each diagnostic can be anchored on the exact token it concerns.
+fn check_spanned(ast: &AST, source: &str, cursor: &mut usize) -> Result<Signature, Error> {
+    match ast {
+        AST::Scope(obj) => check_spanned(obj, source, cursor),
+        AST::Arrow(obj1, obj2) => {
+            let s1 = check_spanned(obj1, source, cursor)?;
+            let from = *cursor;
+            let s2 = check_spanned(obj2, source, cursor)?;
+            if unify(s1.output, s2.input).is_none() {
+                return Err(Error::TypeMismatch(
+                    leading_span(source, from, obj2),
+                    format!("cannot chain {:?} into {:?}", s1.output, s2.input),
+                ));
+            }
+            Ok(Signature {
+                input: s1.input,
+                output: s2.output,
+            })
+        }
+        AST::Match(obj1, obj2) => {
+            let from = *cursor;
+            let s1 = check_spanned(obj1, source, cursor)?;
+            let s2 = check_spanned(obj2, source, cursor)?;
+            match (unify(s1.input, s2.input), unify(s1.output, s2.output)) {
+                (Some(input), Some(output)) => Ok(Signature { input, output }),
+                _ => Err(Error::TypeMismatch(
+                    leading_span(source, from, ast),
+                    "match branches have incompatible signatures".to_string(),
+                )),
+            }
+        }
+        AST::Method(args, obj) => {
+            let operator = match obj.as_ref() {
+                AST::Primitive(label)
+                    if matches!(
+                        label.as_str(),
+                        "+" | "-" | "*" | "%" | "==" | "!=" | "=<" | "<"
+                    ) =>
+                {
+                    Some(label.as_str())
+                }
+                _ => None,
+            };
+            let mut promoted = false;
+            for arg in args {
+                let from = *cursor;
+                let s = check_spanned(arg, source, cursor)?;
+                if let Some(op) = operator {
+                    // `%` is integer-only; the other operators promote `Int` to
+                    // `Float`, so they accept either numeric type.
+                    let ok = match s.output {
+                        Type::Int => true,
+                        Type::Float => op != "%",
+                        _ => false,
+                    };
+                    if !ok {
+                        let wanted = if op == "%" { "Int" } else { "numeric" };
+                        return Err(Error::TypeMismatch(
+                            leading_span(source, from, arg),
+                            format!("`{}` operand must be {}", op, wanted),
+                        ));
+                    }
+                    promoted |= s.output == Type::Float;
+                }
+            }
+            let result = check_spanned(obj, source, cursor)?;
+            // A value-producing operator over a `Float` operand yields a
+            // `Float`, mirroring the interpreter's Int→Float promotion.
+            match operator {
+                Some("+" | "-" | "*") if promoted => Ok(Signature {
+                    input: result.input,
+                    output: Type::Float,
+                }),
+                _ => Ok(result),
+            }
+        }
+        AST::Primitive(label) => {
+            let span = consume(source, cursor, label);
+            signature(label).ok_or_else(|| {
+                Error::TypeMismatch(span, format!("unknown primitive `{}`", label))
+            })
+        }
+        AST::Literal(contents) => {
+            consume(source, cursor, &format!(r#""{}""#, contents));
+            Ok(Signature {
+                input: Type::Void,
+                output: Type::Str,
+            })
+        }
+        AST::Variable(label) => {
+            consume(source, cursor, &format!(r"\{}", label));
+            Ok(Signature {
+                input: Type::Any,
+                output: Type::Any,
+            })
+        }
+    }
+}
+
 struct Interpreter {
     storage: HashMap<String, DataInterpreter>,
+    definitions: HashMap<String, AST>,
+    trap: Option<Error>,
 }
 
 impl Interpreter {
     fn new(storage: HashMap<String, DataInterpreter>) -> Self {
-        Interpreter { storage }
+        Interpreter {
+            storage,
+            definitions: HashMap::new(),
+            trap: None,
+        }
     }
 
     fn interpret(
@@ -83,12 +376,29 @@ impl Interpreter {
                 .or_else(|| self.interpret(args, obj2, stream)),
             AST::Method(args, obj) => self.interpret(args, obj, stream),
             AST::Primitive(label) => self.interpret_primitive(args, label, stream),
-            AST::Variable(label) => todo!(),
+            AST::Variable(label) => match self.definitions.get(label) {
+                Some(body) => {
+                    let body = body.clone();
+                    self.interpret(args, &body, stream)
+                }
+                None => {
+                    self.trap = Some(Error::UnboundName(0..0, label.clone()));
+                    None
+                }
+            },
             AST::Literal(contents) => self.interpret_literal(args, contents, stream),
             AST::Scope(obj) => self.interpret(args, obj, stream),
         }
     }
 
+    /// Record a runtime trap and fail the current pipeline. The trap carries
+    /// no source span (`0..0`), so its report points at the start of the
+    /// source.
+    fn trap(&mut self, message: &str) -> Option<DataInterpreter> {
+        self.trap = Some(Error::RuntimeTrap(0..0, message.to_string()));
+        None
+    }
+
     fn interpret_literal(
         &mut self,
         args: &[AST],
@@ -97,7 +407,7 @@ impl Interpreter {
     ) -> Option<DataInterpreter> {
         match stream == DataInterpreter::Void() && args.is_empty() {
             true => Some(DataInterpreter::Str(contents.to_string())),
-            false => panic!(),
+            false => self.trap("a literal requires an empty stream"),
         }
     }
 
@@ -110,109 +420,270 @@ impl Interpreter {
         match label {
             "int" => match stream {
                 DataInterpreter::Int(i) => Some(DataInterpreter::Int(i)),
-                DataInterpreter::Str(s) => Some(DataInterpreter::Int(s.parse::<i64>().ok()?)),
-                DataInterpreter::Void() => panic!(),
+                DataInterpreter::Float(f) => Some(DataInterpreter::Int(f.to_bigint()?)),
+                DataInterpreter::Str(s) => Some(DataInterpreter::Int(s.parse::<BigInt>().ok()?)),
+                DataInterpreter::Void() => self.trap("`int` requires a value, not an empty stream"),
+            },
+            "float" => match stream {
+                DataInterpreter::Int(i) => Some(DataInterpreter::Float(BigDecimal::from(i))),
+                DataInterpreter::Float(f) => Some(DataInterpreter::Float(f)),
+                DataInterpreter::Str(s) => {
+                    Some(DataInterpreter::Float(s.parse::<BigDecimal>().ok()?))
+                }
+                DataInterpreter::Void() => self.trap("`float` requires a value, not an empty stream"),
             },
             "str" => match stream {
                 DataInterpreter::Int(i) => Some(DataInterpreter::Str(i.to_string())),
+                DataInterpreter::Float(f) => Some(DataInterpreter::Str(f.to_string())),
                 DataInterpreter::Str(s) => Some(DataInterpreter::Str(s)),
-                DataInterpreter::Void() => panic!(),
+                DataInterpreter::Void() => self.trap("`str` requires a value, not an empty stream"),
             },
             "output" => {
                 match stream {
                     DataInterpreter::Int(i) => println!("{}", i),
+                    DataInterpreter::Float(f) => println!("{}", f),
                     DataInterpreter::Str(s) => println!("{}", s),
-                    DataInterpreter::Void() => panic!(),
+                    DataInterpreter::Void() => return self.trap("`output` requires a value"),
                 };
                 Some(DataInterpreter::Void())
             }
             "input" => {
                 if stream != DataInterpreter::Void() {
-                    panic!()
+                    return self.trap("`input` requires an empty stream");
                 }
                 let mut input = String::new();
                 std::io::stdin().read_line(&mut input).unwrap();
                 Some(DataInterpreter::Str(input.trim_end().to_string()))
             }
             "store" => {
-                match self.interpret(&[], &args[0], DataInterpreter::Void()) {
+                let [name] = args else {
+                    return self.trap("`store` takes a single slot name");
+                };
+                match self.interpret(&[], name, DataInterpreter::Void()) {
                     Some(DataInterpreter::Str(label)) => self.storage.insert(label, stream),
-                    _ => panic!(),
+                    _ => return self.trap("`store` requires a string slot name"),
                 };
                 Some(DataInterpreter::Void())
             }
+            "def" => {
+                let [name, body] = args else {
+                    return None;
+                };
+                match self.interpret(&[], name, DataInterpreter::Void()) {
+                    Some(DataInterpreter::Str(label)) => {
+                        self.definitions.insert(label, body.clone());
+                    }
+                    _ => return None,
+                }
+                Some(DataInterpreter::Void())
+            }
             "load" => {
                 if stream != DataInterpreter::Void() {
-                    panic!()
+                    return self.trap("`load` requires an empty stream");
                 }
-                match self.interpret(&[], &args[0], DataInterpreter::Void()) {
+                let [name] = args else {
+                    return self.trap("`load` takes a single slot name");
+                };
+                match self.interpret(&[], name, DataInterpreter::Void()) {
                     Some(DataInterpreter::Str(label)) => self.storage.get(&label).cloned(),
-                    _ => panic!(),
+                    _ => self.trap("`load` requires a string slot name"),
                 }
             }
             "+" | "-" | "*" | "%" | "==" | "!=" | "=<" | "<" => {
-                let o1 = self.interpret(&[], &args[0], DataInterpreter::Void())?;
-                let o2 = self.interpret(&[], &args[1], DataInterpreter::Void())?;
-                match (o1, o2) {
-                    (DataInterpreter::Int(i1), DataInterpreter::Int(i2)) => match label {
-                        "+" => Some(DataInterpreter::Int(i1 + i2)),
-                        "-" => Some(DataInterpreter::Int(i1 - i2)),
-                        "*" => Some(DataInterpreter::Int(i1 * i2)),
-                        "%" => Some(DataInterpreter::Int(i1 % i2)),
-                        "==" => (i1 == i2).then_some(DataInterpreter::Void()),
-                        "!=" => (i1 != i2).then_some(DataInterpreter::Void()),
-                        "=<" => (i1 <= i2).then_some(DataInterpreter::Void()),
-                        "<" => (i1 < i2).then_some(DataInterpreter::Void()),
-                        _ => panic!(),
-                    },
-                    _ => panic!(),
-                }
+                let [lhs, rhs] = args else {
+                    return self.trap(&format!("`{}` takes two operands", label));
+                };
+                let o1 = self.interpret(&[], lhs, DataInterpreter::Void())?;
+                let o2 = self.interpret(&[], rhs, DataInterpreter::Void())?;
+                interpret_arithmetic(label, o1, o2)
             }
             "loop" => {
+                let [body] = args else {
+                    return self.trap("`loop` takes a single body");
+                };
                 let mut stream_loop = Some(stream);
                 while let Some(stream) = stream_loop {
-                    stream_loop = self.interpret(&[], &args[0], stream);
+                    stream_loop = self.interpret(&[], body, stream);
                 }
                 stream_loop
             }
             "pass" => Some(stream),
-            _ => panic!(),
+            _ => self.trap(&format!("unknown primitive `{}`", label)),
         }
     }
 }
 
 #[derive(PartialEq, Clone, Debug)]
 enum DataInterpreter {
-    Int(i64),
+    Int(BigInt),
+    Float(BigDecimal),
     Str(String),
     Void(),
 }
 
+fn interpret_arithmetic(
+    label: &str,
+    o1: DataInterpreter,
+    o2: DataInterpreter,
+) -> Option<DataInterpreter> {
+    match (o1, o2) {
+        (DataInterpreter::Int(a), DataInterpreter::Int(b)) => int_arithmetic(label, a, b),
+        (DataInterpreter::Float(a), DataInterpreter::Float(b)) => float_arithmetic(label, a, b),
+        (DataInterpreter::Int(a), DataInterpreter::Float(b)) => {
+            float_arithmetic(label, BigDecimal::from(a), b)
+        }
+        (DataInterpreter::Float(a), DataInterpreter::Int(b)) => {
+            float_arithmetic(label, a, BigDecimal::from(b))
+        }
+        _ => None,
+    }
+}
+
+fn int_arithmetic(label: &str, a: BigInt, b: BigInt) -> Option<DataInterpreter> {
+    match label {
+        "+" => Some(DataInterpreter::Int(a + b)),
+        "-" => Some(DataInterpreter::Int(a - b)),
+        "*" => Some(DataInterpreter::Int(a * b)),
+        "%" => Some(DataInterpreter::Int(a % b)),
+        "==" => (a == b).then_some(DataInterpreter::Void()),
+        "!=" => (a != b).then_some(DataInterpreter::Void()),
+        "=<" => (a <= b).then_some(DataInterpreter::Void()),
+        "<" => (a < b).then_some(DataInterpreter::Void()),
+        _ => None,
+    }
+}
+
+fn float_arithmetic(label: &str, a: BigDecimal, b: BigDecimal) -> Option<DataInterpreter> {
+    match label {
+        "+" => Some(DataInterpreter::Float(a + b)),
+        "-" => Some(DataInterpreter::Float(a - b)),
+        "*" => Some(DataInterpreter::Float(a * b)),
+        "%" => None,
+        "==" => (a == b).then_some(DataInterpreter::Void()),
+        "!=" => (a != b).then_some(DataInterpreter::Void()),
+        "=<" => (a <= b).then_some(DataInterpreter::Void()),
+        "<" => (a < b).then_some(DataInterpreter::Void()),
+        _ => None,
+    }
+}
+
 fn main() {
     let args = env::args().collect::<Vec<String>>();
-    if args.len() != 2 {
-        eprintln!("Usage: hilang <filename>");
-        process::exit(1);
-    }
-    let Ok(mut file) = File::open(&args[1]) else {
-        eprintln!("Cannot open file: {}", &args[1]);
+    let (emit_llvm, path) = match args.as_slice() {
+        [_] => {
+            repl();
+            return;
+        }
+        [_, flag, path] if flag == "--emit-llvm" => (true, path.clone()),
+        [_, path] => (false, path.clone()),
+        _ => {
+            eprintln!("Usage: hilang [--emit-llvm] <filename>");
+            process::exit(1);
+        }
+    };
+    let Ok(mut file) = File::open(&path) else {
+        eprintln!("Cannot open file: {}", &path);
         process::exit(1);
     };
     let mut contents = String::new();
     let Ok(_) = file.read_to_string(&mut contents) else {
-        eprintln!("Cannot read file: {}", &args[1]);
+        eprintln!("Cannot read file: {}", &path);
         process::exit(1);
     };
-    let Ok(ast) = parse_ast(&contents) else {
-        eprintln!("Cannot parse file: {}", &args[1]);
-        process::exit(1);
+    let ast = match parse_ast(&contents) {
+        Ok(ast) => ast,
+        Err(error) => {
+            report(&contents, &error);
+            process::exit(1);
+        }
     };
+    if let Err(error) = check(&ast, &contents) {
+        report(&contents, &error);
+        process::exit(1);
+    }
+    if emit_llvm {
+        match codegen::emit(&ast) {
+            Ok(ir) => print!("{}", ir),
+            Err(message) => {
+                report(&contents, &Error::RuntimeTrap(0..contents.len(), message));
+                process::exit(1);
+            }
+        }
+        return;
+    }
     let mut interpreter = Interpreter::new(HashMap::new());
     let Some(DataInterpreter::Void()) = interpreter.interpret(&[], &ast, DataInterpreter::Void())
     else {
-        eprintln!("Cannot execute successfully: {}", &args[1]);
+        let error = interpreter.trap.take().unwrap_or_else(|| {
+            Error::RuntimeTrap(0..contents.len(), "program did not terminate with Void".to_string())
+        });
+        report(&contents, &anchored(error, &contents));
+        process::exit(1);
+    };
+}
+
+/// A pipeline continues onto the next line when it ends on an operator.
+fn ends_on_operator(line: &str) -> bool {
+    let trimmed = line.trim_end();
+    [
+        "->", "<-", "=<", "==", "!=", ";", "|", "<", "+", "-", "*", "%", ".", ":=",
+    ]
+    .iter()
+    .any(|op| trimmed.ends_with(op))
+}
+
+fn print_value(value: &DataInterpreter) {
+    match value {
+        DataInterpreter::Int(i) => println!("{}", i),
+        DataInterpreter::Float(f) => println!("{}", f),
+        DataInterpreter::Str(s) => println!("{}", s),
+        DataInterpreter::Void() => {}
+    }
+}
+
+fn repl() {
+    let Ok(mut editor) = rustyline::DefaultEditor::new() else {
+        eprintln!("Cannot start line editor");
         process::exit(1);
     };
+    let mut interpreter = Interpreter::new(HashMap::new());
+    loop {
+        let Ok(mut line) = editor.readline("hilang> ") else {
+            break;
+        };
+        while ends_on_operator(&line) {
+            let Ok(continuation) = editor.readline("...     ") else {
+                break;
+            };
+            line.push('\n');
+            line.push_str(&continuation);
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line.as_str());
+        let ast = match parse_ast(&line) {
+            Ok(ast) => ast,
+            Err(error) => {
+                report(&line, &error);
+                continue;
+            }
+        };
+        if let Err(error) = check(&ast, &line) {
+            report(&line, &error);
+            continue;
+        }
+        interpreter.trap = None;
+        match interpreter.interpret(&[], &ast, DataInterpreter::Void()) {
+            Some(value) => print_value(&value),
+            None => {
+                let error = interpreter.trap.take().unwrap_or_else(|| {
+                    Error::RuntimeTrap(0..line.len(), "no result".to_string())
+                });
+                report(&line, &anchored(error, &line));
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -221,7 +692,7 @@ mod tests {
 
     #[test]
     fn test_parse_1() {
-        assert_eq!(parse_ast("{aaa ->bbb }"), Err(()));
+        assert!(parse_ast("{aaa ->bbb }").is_err());
     }
 
     #[test]
@@ -237,10 +708,7 @@ mod tests {
 
     #[test]
     fn test_parse_3() {
-        assert_eq!(
-            parse_ast("{inst1 -> inst2 -> {inst4 <- inst3} -> inst5}"),
-            Err(())
-        );
+        assert!(parse_ast("{inst1 -> inst2 -> {inst4 <- inst3} -> inst5}").is_err());
     }
 
     #[test]
@@ -265,10 +733,7 @@ mod tests {
 
     #[test]
     fn test_parse_5() {
-        assert_eq!(
-            parse_ast("{a=(P -> Q), b={c=(R -> {S <- T}), d={U <- V}}}"),
-            Err(())
-        );
+        assert!(parse_ast("{a=(P -> Q), b={c=(R -> {S <- T}), d={U <- V}}}").is_err());
     }
 
     #[test]
@@ -345,7 +810,7 @@ mod tests {
 
     #[test]
     fn test_parse_9() {
-        assert_eq!(parse_ast("#"), Err(()));
+        assert!(parse_ast("#").is_err());
     }
 
     #[test]
@@ -358,16 +823,16 @@ mod tests {
         let program = r#"("3" -> int) + "a".load -> "b".store -> "b".load"#;
         let ast = parse_ast(program).unwrap();
         let mut interpreter =
-            Interpreter::new(HashMap::from([("a".to_string(), DataInterpreter::Int(5))]));
+            Interpreter::new(HashMap::from([("a".to_string(), DataInterpreter::Int(BigInt::from(5)))]));
         assert_eq!(
             interpreter.interpret(&[], &ast, DataInterpreter::Void()),
-            Some(DataInterpreter::Int(8))
+            Some(DataInterpreter::Int(BigInt::from(8)))
         );
         assert_eq!(
             interpreter.storage,
             HashMap::from([
-                ("a".to_string(), DataInterpreter::Int(5)),
-                ("b".to_string(), DataInterpreter::Int(8))
+                ("a".to_string(), DataInterpreter::Int(BigInt::from(5))),
+                ("b".to_string(), DataInterpreter::Int(BigInt::from(8)))
             ])
         );
     }
@@ -392,8 +857,269 @@ mod tests {
         let ast = parse_ast(program).unwrap();
         let mut interpreter = Interpreter::new(HashMap::new());
         assert_eq!(
-            interpreter.interpret(&[], &ast, DataInterpreter::Int(30)),
-            Some(DataInterpreter::Int(129))
+            interpreter.interpret(&[], &ast, DataInterpreter::Int(BigInt::from(30))),
+            Some(DataInterpreter::Int(BigInt::from(129)))
+        );
+    }
+
+    #[test]
+    fn test_check_1() {
+        let program = r#""3" -> int -> output"#;
+        let ast = parse_ast(program).unwrap();
+        assert_eq!(
+            check(&ast, program),
+            Ok(Signature {
+                input: Type::Void,
+                output: Type::Void
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_2() {
+        let program = r#""a" + "b""#;
+        let ast = parse_ast(program).unwrap();
+        assert!(check(&ast, program).is_err());
+    }
+
+    #[test]
+    fn test_check_3() {
+        let ast = parse_ast("bogus").unwrap();
+        assert!(check(&ast, "bogus").is_err());
+    }
+
+    #[test]
+    fn test_check_4() {
+        // Feeding Void into a sink is rejected before execution.
+        let program = r#""x".store -> output"#;
+        let ast = parse_ast(program).unwrap();
+        assert!(check(&ast, program).is_err());
+    }
+
+    #[test]
+    fn test_check_5() {
+        // `float` yields a `Float`, which promotes under `+` but is rejected
+        // by the integer-only `%`.
+        let program = r#"("2" -> int) + ("3" -> float)"#;
+        let ast = parse_ast(program).unwrap();
+        assert_eq!(check(&ast, program).unwrap().output, Type::Float);
+
+        let program = r#"("5" -> float) % ("2" -> int)"#;
+        let ast = parse_ast(program).unwrap();
+        let error = check(&ast, program).unwrap_err();
+        assert_eq!(&program[error.span().clone()], r#""5""#);
+    }
+
+    #[test]
+    fn test_error_spans_point_at_token() {
+        // A bad `\` underlines the backslash itself.
+        let error = parse_ast(r"\").unwrap_err();
+        assert_eq!(*error.span(), 0..1);
+
+        // An unknown primitive underlines the primitive itself.
+        let program = r#""1" -> bogus"#;
+        let ast = parse_ast(program).unwrap();
+        let error = check(&ast, program).unwrap_err();
+        assert_eq!(&program[error.span().clone()], "bogus");
+
+        // A non-Int operand underlines that operand.
+        let program = r#"("1" -> int) + "oops""#;
+        let ast = parse_ast(program).unwrap();
+        let error = check(&ast, program).unwrap_err();
+        assert_eq!(&program[error.span().clone()], r#""oops""#);
+
+        // A repeated token is anchored on the offending occurrence, not the
+        // first one in the source.
+        let program = r#"("2" -> int) + ("2" -> str)"#;
+        let ast = parse_ast(program).unwrap();
+        let error = check(&ast, program).unwrap_err();
+        let span = error.span().clone();
+        assert_eq!(&program[span.clone()], r#""2""#);
+        assert!(span.start > program.find(r#""2""#).unwrap());
+
+        // A malformed program points at the offending token, not the line.
+        let error = parse_ast("{bad}").unwrap_err();
+        assert_eq!(&"{bad}"[error.span().clone()], "{");
+    }
+
+    #[test]
+    fn test_check_synthetic_token_terminates() {
+        // `:=` lowers to a synthetic `def` primitive with no source spelling;
+        // the span cursor must fall back cleanly instead of searching for a
+        // token that does not exist.
+        let program = r#""id" := pass"#;
+        let ast = parse_ast(program).unwrap();
+        assert!(check(&ast, program).is_ok());
+    }
+
+    #[test]
+    fn test_codegen_1() {
+        let ast = parse_ast(r#""3" -> int -> output"#).unwrap();
+        let ir = codegen::emit(&ast).unwrap();
+        assert!(ir.contains("define i32 @main()"));
+        assert!(ir.contains("store i64 3, i64* %stream"));
+        assert!(ir.contains("call i32 (i8*, ...) @printf"));
+    }
+
+    #[test]
+    fn test_codegen_match_restores_stream() {
+        // The emitted match snapshots %stream and restores it before the
+        // right branch, so both backends run the RHS on the original value.
+        let program = r#"(("1" -> int) < ("0" -> int)) | pass"#;
+        let ast = parse_ast(program).unwrap();
+        let ir = codegen::emit(&ast).unwrap();
+        let rhs = ir
+            .find("match.rhs1:")
+            .expect("match lowers to a right-branch block");
+        let restore = ir
+            .find("store i64 %t")
+            .expect("stream is restored from a snapshot register");
+        assert!(
+            restore > rhs,
+            "the snapshot must be stored back inside the rhs block"
+        );
+    }
+
+    // Differential check: the match-failure path must leave the right branch
+    // looking at the *original* stream, the semantics the LLVM backend mirrors
+    // via its save/restore of %stream. (End-to-end execution of the emitted IR
+    // is exercised by the out-of-tree harness that links against libc.)
+    #[test]
+    fn test_match_failure_preserves_stream() {
+        let program = r#"(("1" -> int) < ("0" -> int)) | pass"#;
+        let ast = parse_ast(program).unwrap();
+        let mut interpreter = Interpreter::new(HashMap::new());
+        assert_eq!(
+            interpreter.interpret(&[], &ast, DataInterpreter::Int(BigInt::from(42))),
+            Some(DataInterpreter::Int(BigInt::from(42)))
+        );
+    }
+
+    #[test]
+    fn test_codegen_loop_fails_into_enclosing_branch() {
+        // `loop` always yields None, so after its body stops matching the
+        // emitted code must branch to the enclosing failure target — here the
+        // match's right branch — instead of falling through as a success edge.
+        let program = r#"(("0" -> int) < ("1" -> int)).loop | pass"#;
+        let ast = parse_ast(program).unwrap();
+        let ir = codegen::emit(&ast).unwrap();
+        let end = ir.find("loop.end").expect("loop lowers an end block");
+        assert!(
+            ir[end..].contains("br label %match.rhs"),
+            "loop.end must branch into the enclosing failure target"
+        );
+    }
+
+    // Differential: the i64 backend refuses literals and primitives it cannot
+    // represent rather than lowering them to a different value. Arithmetic
+    // whose result overflows 64 bits still wraps in the emitted IR, where the
+    // interpreter keeps the exact value. (Numeric equivalence of the programs it does
+    // accept is exercised by the out-of-tree harness that links against libc.)
+    #[test]
+    fn test_codegen_refuses_unrepresentable() {
+        // `float` has no i64 lowering.
+        let ast = parse_ast(r#"("3" -> float) -> output"#).unwrap();
+        assert!(codegen::emit(&ast).is_err());
+        // A literal wider than i64 is refused rather than truncated.
+        let ast = parse_ast(r#""99999999999999999999" -> output"#).unwrap();
+        assert!(codegen::emit(&ast).is_err());
+        // Integer-only pipelines the interpreter accepts still lower cleanly.
+        let ast = parse_ast(r#""3" -> int -> output"#).unwrap();
+        assert!(codegen::emit(&ast).is_ok());
+    }
+
+    #[test]
+    fn test_interpreter_missing_operands_trap() {
+        // A sink used without its operands records a trap instead of panicking,
+        // so the REPL can report it and keep going.
+        let ast = parse_ast(r#""x" -> store"#).unwrap();
+        let mut interpreter = Interpreter::new(HashMap::new());
+        assert_eq!(
+            interpreter.interpret(&[], &ast, DataInterpreter::Void()),
+            None
+        );
+        assert!(interpreter.trap.is_some());
+    }
+
+    // Differential over the interpreter programs: the i64 backend must accept
+    // exactly the integer-domain programs the interpreter evaluates and refuse
+    // the rest, so the two backends never silently disagree. (Numeric equality
+    // of the accepted programs is exercised by the out-of-tree harness that
+    // executes the emitted IR against libc; the arbitrary-precision interpreter
+    // and the i64 backend still diverge on values that overflow 64 bits, which
+    // the backend cannot represent and so does not claim to.)
+    #[test]
+    fn test_codegen_differential_over_interpreter_programs() {
+        // Integer-domain: the interpreter yields a value and codegen lowers it.
+        let program = r#"("2" -> int) + ("3" -> int) -> output"#;
+        let ast = parse_ast(program).unwrap();
+        let mut interpreter = Interpreter::new(HashMap::new());
+        assert_eq!(
+            interpreter.interpret(&[], &ast, DataInterpreter::Void()),
+            Some(DataInterpreter::Void())
+        );
+        assert!(codegen::emit(&ast).is_ok());
+
+        // Outside the i64 integer domain, codegen refuses rather than diverge.
+        for program in [
+            r#"("2" -> int) + ("3" -> float)"#, // a float value
+            r#"\echo"#,                          // an unbound name
+            r#""id" := pass"#,                   // a definition
+        ] {
+            let ast = parse_ast(program).unwrap();
+            assert!(codegen::emit(&ast).is_err(), "{} must be refused", program);
+        }
+    }
+
+    #[test]
+    fn test_interpreter_3() {
+        let ast = parse_ast(r#"\echo"#).unwrap();
+        let mut interpreter = Interpreter::new(HashMap::new());
+        assert_eq!(
+            interpreter.interpret(&[], &ast, DataInterpreter::Int(BigInt::from(7))),
+            None
+        );
+        interpreter
+            .definitions
+            .insert("echo".to_string(), AST::Primitive("pass".to_string()));
+        assert_eq!(
+            interpreter.interpret(&[], &ast, DataInterpreter::Int(BigInt::from(7))),
+            Some(DataInterpreter::Int(BigInt::from(7)))
+        );
+    }
+
+    #[test]
+    fn test_repl_continuation() {
+        assert!(ends_on_operator(r#""5" -> int +"#));
+        assert!(ends_on_operator(r#""x".load ->"#));
+        assert!(!ends_on_operator(r#""x".load"#));
+    }
+
+    #[test]
+    fn test_interpreter_def() {
+        let program = r#""id" := pass;
+"7" -> int -> \id"#;
+        let ast = parse_ast(program).unwrap();
+        let mut interpreter = Interpreter::new(HashMap::new());
+        assert_eq!(
+            interpreter.interpret(&[], &ast, DataInterpreter::Void()),
+            Some(DataInterpreter::Int(BigInt::from(7)))
+        );
+        assert!(interpreter.definitions.contains_key("id"));
+    }
+
+    #[test]
+    fn test_interpreter_4() {
+        let ast = parse_ast(r#"("2" -> int) + ("3" -> float)"#).unwrap();
+        let mut interpreter = Interpreter::new(HashMap::new());
+        assert_eq!(
+            interpreter.interpret(&[], &ast, DataInterpreter::Void()),
+            Some(DataInterpreter::Float(BigDecimal::from(5)))
+        );
+        let ast = parse_ast(r#"("5" -> float) % ("2" -> int)"#).unwrap();
+        assert_eq!(
+            interpreter.interpret(&[], &ast, DataInterpreter::Void()),
+            None
         );
     }
 }